@@ -13,22 +13,214 @@ pub use sync::*;
 use crate::{
     error::{Error, FaunaErrors},
     expr::Expr,
+    query::Paginate,
 };
-use futures::{future, stream::Stream, Future};
-use http::header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE};
+use futures::{
+    future::{self, Either, Loop},
+    stream,
+    stream::Stream,
+    Future,
+};
+use http::header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, RETRY_AFTER};
 use hyper::{client::HttpConnector, Body, StatusCode, Uri};
 use hyper_tls::HttpsConnector;
 use serde_json;
-use std::{borrow::Cow, time::Duration};
-use tokio_timer::Timeout;
+use std::{
+    borrow::Cow,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio_timer::{Delay, Timeout};
 
 type Transport = hyper::Client<HttpsConnector<HttpConnector>>;
 
+/// A structured event emitted around each request when an
+/// [`on_event`](struct.ClientBuilder.html#method.on_event) handler is
+/// configured. It lets callers measure latency, payload sizes, retries and
+/// error rates and bridge them into metrics or tracing without this crate
+/// taking a hard dependency on any such system.
+#[derive(Debug)]
+pub enum ClientEvent<'a> {
+    /// A request is about to be sent, carrying its serialized byte length.
+    RequestStarted { request_bytes: usize },
+    /// A response was received, with its status, the elapsed time since the
+    /// attempt started, and the response body's byte length.
+    ResponseReceived {
+        status: StatusCode,
+        elapsed: Duration,
+        response_bytes: usize,
+    },
+    /// A transient failure is about to be retried after `delay`.
+    RetryAttempted { attempt: u32, delay: Duration },
+    /// The request failed for good, with the total elapsed time.
+    RequestFailed {
+        error: &'a Error,
+        elapsed: Duration,
+    },
+}
+
+/// The handler invoked with each [`ClientEvent`](enum.ClientEvent.html).
+type EventHandler = Arc<dyn Fn(ClientEvent) + Send + Sync + 'static>;
+
+/// The default base delay between retries.
+const DEFAULT_RETRY_BASE: Duration = Duration::from_millis(100);
+/// The default ceiling on a single retry delay.
+const DEFAULT_RETRY_MAX: Duration = Duration::from_secs(10);
+
+/// Controls how [`Client`](struct.Client.html) retries transient failures.
+///
+/// Retries apply only to idempotent, transient outcomes ([`ConnectionError`],
+/// [`TimeoutError`], HTTP `429`, `503` and `500`). A rejected query (`400`) or
+/// bad secret (`401`) short-circuits immediately.
+///
+/// [`ConnectionError`]: ../error/enum.Error.html#variant.ConnectionError
+/// [`TimeoutError`]: ../error/enum.Error.html#variant.TimeoutError
+#[derive(Debug, Clone)]
+pub struct RetryOptions {
+    max_retries: u32,
+    base: Duration,
+    max: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            base: DEFAULT_RETRY_BASE,
+            max: DEFAULT_RETRY_MAX,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryOptions {
+    /// The delay to wait before the retry following `attempt` (zero-indexed).
+    ///
+    /// A server-suggested delay (e.g. a `Retry-After` header) is always
+    /// preferred over the computed backoff. Otherwise the delay is
+    /// `min(max, base * 2^attempt)`, reduced to a uniformly random value in
+    /// `[0, delay]` (whole milliseconds) when full jitter is enabled.
+    fn backoff(&self, attempt: u32, server_delay: Option<Duration>) -> Duration {
+        if let Some(delay) = server_delay {
+            return delay;
+        }
+
+        let exponential = self
+            .base
+            .checked_mul(2u32.saturating_pow(attempt))
+            .unwrap_or(self.max);
+
+        let capped = std::cmp::min(exponential, self.max);
+
+        if self.jitter {
+            let millis = capped.as_millis() as u64;
+            let jittered = if millis == 0 {
+                0
+            } else {
+                rand::random::<u64>() % (millis + 1)
+            };
+
+            Duration::from_millis(jittered)
+        } else {
+            capped
+        }
+    }
+}
+
+/// Reads the `{ "resource": [ ... ] }` envelope Fauna returns for a batch,
+/// where each element is a bare per-query result value rather than its own
+/// `resource`-wrapped object.
+#[derive(Debug, Clone, Deserialize)]
+struct BatchResource {
+    resource: Vec<serde_json::Value>,
+}
+
+/// The direction in which [`Client::paginate`](struct.Client.html#method.paginate)
+/// follows page cursors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaginateDirection {
+    /// Follow the `after` cursor, scanning the set front to back.
+    Forward,
+    /// Follow the `before` cursor, scanning the set back to front.
+    Backward,
+}
+
+impl Default for PaginateDirection {
+    fn default() -> Self {
+        PaginateDirection::Forward
+    }
+}
+
+impl PaginateDirection {
+    /// The cursor field Fauna returns for this direction.
+    fn cursor_field(self) -> &'static str {
+        match self {
+            PaginateDirection::Forward => "after",
+            PaginateDirection::Backward => "before",
+        }
+    }
+}
+
+/// Configures a [`Client::paginate`](struct.Client.html#method.paginate) scan.
+#[derive(Debug, Clone, Default)]
+pub struct PaginateOptions {
+    size: Option<u64>,
+    direction: PaginateDirection,
+}
+
+impl PaginateOptions {
+    /// The number of entries returned per page.
+    pub fn size(&mut self, size: u64) -> &mut Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// The direction in which cursors are followed. Default:
+    /// [`Forward`](enum.PaginateDirection.html#variant.Forward) (`after`).
+    pub fn direction(&mut self, direction: PaginateDirection) -> &mut Self {
+        self.direction = direction;
+        self
+    }
+}
+
+/// Per-request overrides applied on top of the client's defaults.
+///
+/// Fauna access is commonly scoped by logging in as an instance (`Login`) and
+/// running subsequent queries under the returned token, or by using scoped
+/// keys for a specific database or role. [`QueryOptions`](struct.QueryOptions.html)
+/// lets a single request carry its own secret without holding a
+/// [`Client`](struct.Client.html) per end-user.
+#[derive(Debug, Clone, Default)]
+pub struct QueryOptions {
+    authorization: Option<String>,
+    last_seen_txn: Option<u64>,
+}
+
+impl QueryOptions {
+    /// Override the secret for this request, impersonating an end-user token
+    /// obtained from `Login` or a scoped key.
+    pub fn secret<'a>(&mut self, secret: impl Into<Cow<'a, str>>) -> &mut Self {
+        let secret_b64 = base64::encode(&format!("{}:", secret.into()));
+        self.authorization = Some(format!("Basic {}", secret_b64));
+        self
+    }
+
+    /// The transaction timestamp from a previous response, passed as
+    /// `X-Last-Seen-Txn` to get read-your-writes consistency.
+    pub fn last_seen_txn(&mut self, txn: u64) -> &mut Self {
+        self.last_seen_txn = Some(txn);
+        self
+    }
+}
+
 /// For building a new Fauna client.
 pub struct ClientBuilder<'a> {
     uri: Cow<'a, str>,
     secret: Cow<'a, str>,
     timeout: Duration,
+    retry: RetryOptions,
+    on_event: Option<EventHandler>,
 }
 
 impl<'a> ClientBuilder<'a> {
@@ -45,6 +237,38 @@ impl<'a> ClientBuilder<'a> {
         self
     }
 
+    /// The number of times a transient failure is retried before giving up.
+    /// Default: `0` (no retries).
+    pub fn max_retries(&mut self, max_retries: u32) -> &mut Self {
+        self.retry.max_retries = max_retries;
+        self
+    }
+
+    /// The base delay used for exponential backoff. Default: `100ms`.
+    pub fn retry_base_delay(&mut self, base: Duration) -> &mut Self {
+        self.retry.base = base;
+        self
+    }
+
+    /// The ceiling on a single retry delay. Default: `10 seconds`.
+    pub fn retry_max_delay(&mut self, max: Duration) -> &mut Self {
+        self.retry.max = max;
+        self
+    }
+
+    /// Whether to apply full jitter to the backoff delay. Default: `true`.
+    pub fn retry_jitter(&mut self, jitter: bool) -> &mut Self {
+        self.retry.jitter = jitter;
+        self
+    }
+
+    /// Register a callback invoked with a [`ClientEvent`](enum.ClientEvent.html)
+    /// at each stage of a request, for metrics and tracing.
+    pub fn on_event(&mut self, handler: impl Fn(ClientEvent) + Send + Sync + 'static) -> &mut Self {
+        self.on_event = Some(Arc::new(handler));
+        self
+    }
+
     /// Creates the client.
     pub fn build(self) -> crate::Result<Client> {
         let mut builder = hyper::Client::builder();
@@ -57,6 +281,8 @@ impl<'a> ClientBuilder<'a> {
             uri: self.uri.parse()?,
             timeout: self.timeout,
             authorization: format!("Basic {}", secret_b64),
+            retry: self.retry,
+            on_event: self.on_event,
         })
     }
 
@@ -71,11 +297,14 @@ impl<'a> ClientBuilder<'a> {
 ///
 /// Do not create new clients for every request to prevent
 /// spamming Fauna servers with new connections.
+#[derive(Clone)]
 pub struct Client {
     transport: Transport,
     uri: Uri,
     timeout: Duration,
     authorization: String,
+    retry: RetryOptions,
+    on_event: Option<EventHandler>,
 }
 
 impl Client {
@@ -86,11 +315,45 @@ impl Client {
             uri: Cow::from("https://db.fauna.com"),
             secret: secret.into(),
             timeout: Duration::new(60, 0),
+            retry: RetryOptions::default(),
+            on_event: None,
         }
     }
 
     /// Send a query to Fauna servers and parsing the response.
     pub fn query<'a, Q>(&self, query: Q) -> FutureResponse<Response>
+    where
+        Q: Into<Expr<'a>>,
+    {
+        self.query_with_options(query, QueryOptions::default())
+    }
+
+    /// Send a query authorized by `secret` instead of the client's secret.
+    ///
+    /// This lets a server run a query under an end-user token obtained from
+    /// `Login`, or a scoped key, without building a separate
+    /// [`Client`](struct.Client.html) for each identity.
+    pub fn query_with_secret<'a, 'b, Q>(
+        &self,
+        query: Q,
+        secret: impl Into<Cow<'b, str>>,
+    ) -> FutureResponse<Response>
+    where
+        Q: Into<Expr<'a>>,
+    {
+        let mut options = QueryOptions::default();
+        options.secret(secret);
+
+        self.query_with_options(query, options)
+    }
+
+    /// Send a query with per-request [`QueryOptions`](struct.QueryOptions.html),
+    /// such as an overriding secret or an `X-Last-Seen-Txn` timestamp.
+    pub fn query_with_options<'a, Q>(
+        &self,
+        query: Q,
+        options: QueryOptions,
+    ) -> FutureResponse<Response>
     where
         Q: Into<Expr<'a>>,
     {
@@ -99,79 +362,416 @@ impl Client {
 
         trace!("Querying with: {:?}", &payload_json);
 
-        self.request(self.build_request(payload_json), |body| {
+        self.request(payload_json, options, |body| {
             serde_json::from_str(&body).unwrap()
         })
     }
 
-    fn request<F, T>(&self, request: hyper::Request<Body>, f: F) -> FutureResponse<T>
+    /// Send several queries to Fauna in a single HTTP round-trip.
+    ///
+    /// The expressions are serialized as a top-level JSON array and Fauna
+    /// responds with a parallel array of per-query result values, preserving
+    /// the order of the input. This saves a round-trip when issuing a handful
+    /// of independent queries, such as creating a set of classes together with
+    /// their [CreateIndex](../query/struct.CreateIndex.html) calls while
+    /// bootstrapping a schema.
+    ///
+    /// Fauna evaluates the array as a single transaction: if any sub-query
+    /// fails the whole batch is rejected with a top-level error (e.g.
+    /// [`BadRequest`](../error/enum.Error.html#variant.BadRequest)) and no
+    /// partial results are returned. The successful results therefore only
+    /// come back when every sub-query succeeds.
+    pub fn query_batch<'a, I>(&self, queries: I) -> FutureResponse<Vec<serde_json::Value>>
+    where
+        I: IntoIterator<Item = Expr<'a>>,
+    {
+        let queries: Vec<Expr<'a>> = queries.into_iter().collect();
+        let payload_json = serde_json::to_string(&queries).unwrap();
+
+        trace!("Querying batch with: {:?}", &payload_json);
+
+        self.request(payload_json, QueryOptions::default(), |body| {
+            let batch: BatchResource = serde_json::from_str(&body).unwrap();
+            batch.resource
+        })
+    }
+
+    /// Scan a set or index, following page cursors until the set is exhausted.
+    ///
+    /// The initial [`Paginate`](../query/struct.Paginate.html) is issued with
+    /// the configured page `size`, then the cursor returned in the response
+    /// (`after` for [`Forward`](enum.PaginateDirection.html#variant.Forward),
+    /// `before` for [`Backward`](enum.PaginateDirection.html#variant.Backward))
+    /// is fed back into the same query to fetch the following page. A response
+    /// without a cursor terminates the stream; it is treated as end-of-stream
+    /// rather than an error, so the caller can scan a large index without
+    /// tracking cursors by hand.
+    pub fn paginate<'a>(
+        &'a self,
+        set: impl Into<Expr<'a>>,
+        options: PaginateOptions,
+    ) -> impl Stream<Item = Response, Error = Error> + 'a {
+        let set = set.into();
+        let cursor_field = options.direction.cursor_field();
+
+        stream::unfold(Some(None), move |state| {
+            let cursor = match state {
+                // `Some(cursor)` still has a page to fetch; `None` signals that
+                // the previous response had no cursor, ending the stream.
+                Some(cursor) => cursor,
+                None => return None,
+            };
+
+            let mut paginate = Paginate::new(set.clone());
+
+            if let Some(size) = options.size {
+                paginate.size(size);
+            }
+
+            if let Some(ref cursor) = cursor {
+                match options.direction {
+                    PaginateDirection::Forward => paginate.after(Expr::from(cursor.clone())),
+                    PaginateDirection::Backward => paginate.before(Expr::from(cursor.clone())),
+                };
+            }
+
+            let next = self.query(paginate).map(move |response| {
+                let cursor = response.resource.get(cursor_field).cloned();
+                let next_state = cursor.map(Some);
+
+                (response, next_state)
+            });
+
+            Some(next)
+        })
+    }
+
+    fn request<F, T>(&self, payload: String, options: QueryOptions, f: F) -> FutureResponse<T>
+    where
+        T: Send + Sync + 'static,
+        F: Fn(String) -> T + Send + Sync + 'static,
+    {
+        let client = self.clone();
+        let options = Arc::new(options);
+        let f = Arc::new(f);
+        let started = Instant::now();
+
+        // Wrap the `Timeout`-ed attempt in a retry loop. Only transient
+        // outcomes are retried, using exponential backoff (with full jitter
+        // unless disabled); a
+        // non-retryable error (401, 400, ...) short-circuits immediately.
+        let retried = future::loop_fn(0u32, move |attempt| {
+            let client = client.clone();
+            let options = options.clone();
+            let f = f.clone();
+
+            client.attempt(payload.clone(), options, f).then(move |result| match result {
+                Ok(value) => Either::A(future::ok(Loop::Break(value))),
+                Err((error, server_delay)) => {
+                    if error.is_retryable() && attempt < client.retry.max_retries {
+                        let delay = client.retry.backoff(attempt, server_delay);
+                        trace!("Retrying after {:?} (attempt {})", delay, attempt + 1);
+                        client.emit(ClientEvent::RetryAttempted {
+                            attempt: attempt + 1,
+                            delay,
+                        });
+
+                        let wait = Delay::new(Instant::now() + delay)
+                            .map_err(|_| Error::TimeoutError)
+                            .map(move |_| Loop::Continue(attempt + 1));
+
+                        Either::B(wait)
+                    } else {
+                        client.emit(ClientEvent::RequestFailed {
+                            error: &error,
+                            elapsed: started.elapsed(),
+                        });
+
+                        Either::A(future::err(error))
+                    }
+                }
+            })
+        });
+
+        FutureResponse(Box::new(retried))
+    }
+
+    /// Invoke the configured [`on_event`](struct.ClientBuilder.html#method.on_event)
+    /// handler, if any.
+    fn emit(&self, event: ClientEvent) {
+        if let Some(ref handler) = self.on_event {
+            handler(event);
+        }
+    }
+
+    /// Perform a single request attempt, surfacing any server-suggested retry
+    /// delay alongside the error so the retry loop can prefer it.
+    fn attempt<F, T>(
+        &self,
+        payload: String,
+        options: Arc<QueryOptions>,
+        f: Arc<F>,
+    ) -> impl Future<Item = T, Error = (Error, Option<Duration>)> + Send
     where
         T: Send + Sync + 'static,
-        F: FnOnce(String) -> T + Send + Sync + 'static,
+        F: Fn(String) -> T + Send + Sync + 'static,
     {
+        let started = Instant::now();
+        let handler = self.on_event.clone();
+
+        if let Some(ref handler) = handler {
+            handler(ClientEvent::RequestStarted {
+                request_bytes: payload.len(),
+            });
+        }
+
         let send_request = self
             .transport
-            .request(request)
-            .map_err(|e| Error::ConnectionError(e.into()));
+            .request(self.build_request(payload, &options))
+            .map_err(|e| (Error::ConnectionError(e.into()), None));
 
         let requesting = send_request.and_then(move |response| {
             trace!("Client::call got response status {}", response.status());
 
             let status = response.status();
+            let server_delay = retry_after(&response);
 
             let get_body = response
                 .into_body()
-                .map_err(|e| Error::ConnectionError(e.into()))
+                .map_err(|e| (Error::ConnectionError(e.into()), None))
                 .concat2();
 
             get_body.and_then(move |body_chunk| {
                 if let Ok(body) = String::from_utf8(body_chunk.to_vec()) {
                     trace!("Got response: {:?}", &body);
 
+                    if let Some(ref handler) = handler {
+                        handler(ClientEvent::ResponseReceived {
+                            status,
+                            elapsed: started.elapsed(),
+                            response_bytes: body.len(),
+                        });
+                    }
+
+                    // Parse the `errors` array into the Fauna taxonomy,
+                    // degrading to a raw-body `DatabaseError` if the body is
+                    // not in the shape we expect rather than panicking.
+                    let as_error = |wrap: fn(FaunaErrors) -> Error| {
+                        match serde_json::from_str::<FaunaErrors>(&body) {
+                            Ok(errors) => wrap(errors),
+                            Err(_) => Error::DatabaseError {
+                                status: status.as_u16(),
+                                body: body.clone(),
+                            },
+                        }
+                    };
+
                     match status {
                         s if s.is_success() => future::ok(f(body)),
-                        StatusCode::UNAUTHORIZED => future::err(Error::Unauthorized),
+                        StatusCode::UNAUTHORIZED => {
+                            future::err((Error::Unauthorized, server_delay))
+                        }
                         StatusCode::BAD_REQUEST => {
-                            let errors: FaunaErrors = serde_json::from_str(&body).unwrap();
-                            future::err(Error::BadRequest(errors))
+                            future::err((as_error(Error::BadRequest), server_delay))
                         }
                         StatusCode::NOT_FOUND => {
-                            let errors: FaunaErrors = serde_json::from_str(&body).unwrap();
-                            future::err(Error::NotFound(errors))
+                            future::err((as_error(Error::NotFound), server_delay))
+                        }
+                        StatusCode::TOO_MANY_REQUESTS => {
+                            future::err((as_error(Error::TooManyRequests), server_delay))
+                        }
+                        StatusCode::SERVICE_UNAVAILABLE => {
+                            future::err((as_error(Error::Unavailable), server_delay))
                         }
-                        _ => future::err(Error::DatabaseError(body)),
+                        StatusCode::INTERNAL_SERVER_ERROR => {
+                            future::err((as_error(Error::InternalServerError), server_delay))
+                        }
+                        _ => future::err((
+                            Error::DatabaseError {
+                                status: status.as_u16(),
+                                body,
+                            },
+                            server_delay,
+                        )),
                     }
                 } else {
-                    future::err(Error::EmptyResponse)
+                    future::err((Error::EmptyResponse, server_delay))
                 }
             })
         });
 
-        let with_timeout = Timeout::new(requesting, self.timeout).map_err(|e| {
+        Timeout::new(requesting, self.timeout).map_err(|e| {
             if e.is_timer() {
-                Error::TimeoutError
+                (Error::TimeoutError, None)
             } else {
                 match e.into_inner() {
                     Some(error) => error,
-                    None => Error::Other,
+                    None => (Error::Other, None),
                 }
             }
-        });
-
-        FutureResponse(Box::new(with_timeout))
+        })
     }
 
-    fn build_request(&self, payload: String) -> hyper::Request<Body> {
+    fn build_request(&self, payload: String, options: &QueryOptions) -> hyper::Request<Body> {
         let mut builder = hyper::Request::builder();
 
         builder.uri(&self.uri);
         builder.method("POST");
 
+        let authorization = options
+            .authorization
+            .as_ref()
+            .unwrap_or(&self.authorization);
+
         builder.header(CONTENT_LENGTH, format!("{}", payload.len()).as_bytes());
         builder.header(CONTENT_TYPE, "application/json");
-        builder.header(AUTHORIZATION, self.authorization.as_bytes());
+        builder.header(AUTHORIZATION, authorization.as_bytes());
         builder.header("X-FaunaDB-API-Version", "2.1");
 
+        if let Some(txn) = options.last_seen_txn {
+            builder.header("X-Last-Seen-Txn", format!("{}", txn).as_bytes());
+        }
+
         builder.body(Body::from(payload)).unwrap()
     }
 }
+
+/// Extract a server-suggested retry delay from a response's `Retry-After`
+/// header, interpreted as a whole number of seconds.
+///
+/// Only the delta-seconds form is recognized; the alternative HTTP-date form
+/// (`Retry-After: <http-date>`) is not parsed and yields `None`, in which case
+/// the computed exponential backoff is used instead.
+fn retry_after<B>(response: &hyper::Response<B>) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_batch_resource_unwraps_resource_array() {
+        let body = r#"{"resource":[{"a":1},"two",3]}"#;
+        let batch: BatchResource = serde_json::from_str(body).unwrap();
+
+        assert_eq!(
+            batch.resource,
+            vec![json!({ "a": 1 }), json!("two"), json!(3)],
+        );
+    }
+
+    #[test]
+    fn test_paginate_direction_cursor_field() {
+        assert_eq!(PaginateDirection::default(), PaginateDirection::Forward);
+        assert_eq!(PaginateDirection::Forward.cursor_field(), "after");
+        assert_eq!(PaginateDirection::Backward.cursor_field(), "before");
+    }
+
+    #[test]
+    fn test_paginate_options_builder() {
+        let mut options = PaginateOptions::default();
+        options.size(50).direction(PaginateDirection::Backward);
+
+        assert_eq!(options.size, Some(50));
+        assert_eq!(options.direction, PaginateDirection::Backward);
+    }
+
+    #[test]
+    fn test_backoff_prefers_server_delay() {
+        let retry = RetryOptions::default();
+        let server = Duration::from_secs(7);
+
+        assert_eq!(retry.backoff(3, Some(server)), server);
+    }
+
+    #[test]
+    fn test_backoff_exponential_without_jitter() {
+        let retry = RetryOptions {
+            max_retries: 5,
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(10),
+            jitter: false,
+        };
+
+        assert_eq!(retry.backoff(0, None), Duration::from_millis(100));
+        assert_eq!(retry.backoff(1, None), Duration::from_millis(200));
+        assert_eq!(retry.backoff(3, None), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn test_backoff_caps_at_max() {
+        let retry = RetryOptions {
+            max_retries: 20,
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+            jitter: false,
+        };
+
+        assert_eq!(retry.backoff(30, None), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_backoff_jitter_stays_within_bound() {
+        let retry = RetryOptions {
+            max_retries: 5,
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(10),
+            jitter: true,
+        };
+
+        for attempt in 0..5 {
+            let capped = std::cmp::min(
+                Duration::from_millis(100) * 2u32.pow(attempt),
+                Duration::from_secs(10),
+            );
+
+            assert!(retry.backoff(attempt, None) <= capped);
+        }
+    }
+
+    #[test]
+    fn test_query_options_secret_encoding() {
+        let mut options = QueryOptions::default();
+        options.secret("mysecret");
+
+        assert_eq!(
+            options.authorization,
+            Some(String::from("Basic bXlzZWNyZXQ6")),
+        );
+    }
+
+    #[test]
+    fn test_query_options_last_seen_txn() {
+        let mut options = QueryOptions::default();
+        options.last_seen_txn(1234);
+
+        assert_eq!(options.last_seen_txn, Some(1234));
+    }
+
+    #[test]
+    fn test_on_event_handler_fires() {
+        use std::sync::{Arc, Mutex};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+
+        let mut builder = Client::builder("secret");
+        builder.on_event(move |event| {
+            if let ClientEvent::RequestStarted { request_bytes } = event {
+                sink.lock().unwrap().push(request_bytes);
+            }
+        });
+
+        let client = builder.build().unwrap();
+        client.emit(ClientEvent::RequestStarted { request_bytes: 42 });
+
+        assert_eq!(*events.lock().unwrap(), vec![42]);
+    }
+}
@@ -0,0 +1,83 @@
+//! A blocking client for Fauna, wrapping the asynchronous [`Client`].
+
+use crate::{
+    client::{Client, QueryOptions, Response},
+    error::Error,
+    expr::Expr,
+};
+use futures::Future;
+use std::{borrow::Cow, sync::Mutex};
+use tokio::runtime::Runtime;
+
+/// A synchronous wrapper around [`Client`](struct.Client.html) that blocks the
+/// calling thread until each query completes.
+///
+/// Create one with
+/// [`ClientBuilder::build_sync`](struct.ClientBuilder.html#method.build_sync).
+pub struct SyncClient {
+    inner: Client,
+    runtime: Mutex<Runtime>,
+}
+
+impl SyncClient {
+    pub(crate) fn new(inner: Client) -> crate::Result<Self> {
+        Ok(Self {
+            inner,
+            runtime: Mutex::new(Runtime::new()?),
+        })
+    }
+
+    /// Blocking equivalent of
+    /// [`Client::query`](struct.Client.html#method.query).
+    pub fn query<'a, Q>(&self, query: Q) -> crate::Result<Response>
+    where
+        Q: Into<Expr<'a>>,
+    {
+        self.block_on(self.inner.query(query))
+    }
+
+    /// Blocking equivalent of
+    /// [`Client::query_batch`](struct.Client.html#method.query_batch).
+    pub fn query_batch<'a, I>(&self, queries: I) -> crate::Result<Vec<serde_json::Value>>
+    where
+        I: IntoIterator<Item = Expr<'a>>,
+    {
+        self.block_on(self.inner.query_batch(queries))
+    }
+
+    /// Blocking equivalent of
+    /// [`Client::query_with_secret`](struct.Client.html#method.query_with_secret).
+    pub fn query_with_secret<'a, 'b, Q>(
+        &self,
+        query: Q,
+        secret: impl Into<Cow<'b, str>>,
+    ) -> crate::Result<Response>
+    where
+        Q: Into<Expr<'a>>,
+    {
+        self.block_on(self.inner.query_with_secret(query, secret))
+    }
+
+    /// Blocking equivalent of
+    /// [`Client::query_with_options`](struct.Client.html#method.query_with_options).
+    pub fn query_with_options<'a, Q>(
+        &self,
+        query: Q,
+        options: QueryOptions,
+    ) -> crate::Result<Response>
+    where
+        Q: Into<Expr<'a>>,
+    {
+        self.block_on(self.inner.query_with_options(query, options))
+    }
+
+    /// Run a future to completion on the client's runtime.
+    fn block_on<F>(&self, future: F) -> crate::Result<F::Item>
+    where
+        F: Future<Error = Error> + Send + 'static,
+        F::Item: Send + 'static,
+    {
+        let mut runtime = self.runtime.lock().unwrap();
+        runtime.block_on(future)
+    }
+}
@@ -0,0 +1,245 @@
+//! Error types for communicating with Fauna.
+
+use std::{error::Error as StdError, fmt};
+
+/// A boxed transport-level error, such as a connection reset or a TLS failure.
+pub type TransportError = Box<dyn StdError + Send + Sync + 'static>;
+
+/// The result type returned throughout the crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The set of errors that can happen when talking to Fauna.
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying connection failed before a response was received.
+    ConnectionError(TransportError),
+    /// The request did not complete within the configured timeout.
+    TimeoutError,
+    /// The provided secret was rejected (HTTP `401`).
+    Unauthorized,
+    /// The query was rejected as invalid (HTTP `400`).
+    BadRequest(FaunaErrors),
+    /// A referenced instance, class or index did not exist (HTTP `404`).
+    NotFound(FaunaErrors),
+    /// Fauna is rate-limiting the key or database (HTTP `429`). Retryable.
+    TooManyRequests(FaunaErrors),
+    /// Fauna is temporarily unable to serve the request (HTTP `503`).
+    /// Retryable.
+    Unavailable(FaunaErrors),
+    /// Fauna hit an internal error handling the request (HTTP `500`).
+    /// Retryable.
+    InternalServerError(FaunaErrors),
+    /// A response arrived with an unexpected status or a body that could not
+    /// be parsed into the Fauna error shape. The raw status and body are kept
+    /// so the caller can inspect them.
+    DatabaseError {
+        status: u16,
+        body: String,
+    },
+    /// The response body was not valid UTF-8.
+    EmptyResponse,
+    /// An otherwise unclassified error.
+    Other,
+}
+
+impl Error {
+    /// Whether retrying the request might succeed. Connection errors,
+    /// timeouts, rate limiting and server-side failures are transient; a
+    /// rejected query or a bad secret is not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::ConnectionError(_)
+            | Error::TimeoutError
+            | Error::TooManyRequests(_)
+            | Error::Unavailable(_)
+            | Error::InternalServerError(_) => true,
+            // A 429, 503 or 500 whose body is not in the Fauna error shape.
+            Error::DatabaseError { status, .. } => match status {
+                429 | 500 | 503 => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::ConnectionError(e) => write!(f, "Connection error: {}", e),
+            Error::TimeoutError => write!(f, "Request timed out"),
+            Error::Unauthorized => write!(f, "Unauthorized: the secret was rejected"),
+            Error::BadRequest(errors) => write!(f, "Bad request: {}", errors),
+            Error::NotFound(errors) => write!(f, "Not found: {}", errors),
+            Error::TooManyRequests(errors) => write!(f, "Too many requests: {}", errors),
+            Error::Unavailable(errors) => write!(f, "Service unavailable: {}", errors),
+            Error::InternalServerError(errors) => write!(f, "Internal server error: {}", errors),
+            Error::DatabaseError { status, body } => {
+                write!(f, "Database error (status {}): {}", status, body)
+            }
+            Error::EmptyResponse => write!(f, "Empty response"),
+            Error::Other => write!(f, "Unknown error"),
+        }
+    }
+}
+
+impl StdError for Error {}
+
+impl From<http::uri::InvalidUri> for Error {
+    fn from(e: http::uri::InvalidUri) -> Self {
+        Error::ConnectionError(e.into())
+    }
+}
+
+impl From<native_tls::Error> for Error {
+    fn from(e: native_tls::Error) -> Self {
+        Error::ConnectionError(e.into())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::ConnectionError(e.into())
+    }
+}
+
+/// The `errors` array Fauna returns in a failed response body.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FaunaErrors {
+    pub errors: Vec<FaunaError>,
+}
+
+impl fmt::Display for FaunaErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let descriptions: Vec<&str> = self
+            .errors
+            .iter()
+            .map(|e| e.description.as_str())
+            .collect();
+
+        write!(f, "{}", descriptions.join("; "))
+    }
+}
+
+/// A single entry in a Fauna [`errors`](struct.FaunaErrors.html) array.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FaunaError {
+    /// The typed `code` describing what went wrong.
+    pub code: FaunaCode,
+    /// A human-readable description of the failure.
+    pub description: String,
+    /// The position within the query where the error occurred.
+    #[serde(default)]
+    pub position: Vec<serde_json::Value>,
+    /// Per-field validation details, present for `validation failed`.
+    #[serde(default)]
+    pub failures: Vec<ValidationFailure>,
+}
+
+/// A per-field validation detail carried by a `validation failed` error.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ValidationFailure {
+    /// The field path that failed validation.
+    #[serde(default)]
+    pub field: Vec<serde_json::Value>,
+    /// The validation error code.
+    pub code: String,
+    /// A human-readable description of the validation failure.
+    pub description: String,
+}
+
+/// The `code` string Fauna attaches to an error, mapped to a typed variant.
+///
+/// Unrecognized codes are preserved verbatim in
+/// [`Unknown`](enum.FaunaCode.html#variant.Unknown) so forward-compatibility
+/// does not require a crate upgrade.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "String")]
+pub enum FaunaCode {
+    InstanceNotFound,
+    InstanceAlreadyExists,
+    ValidationFailed,
+    InstanceNotUnique,
+    Unauthorized,
+    PermissionDenied,
+    TransactionAborted,
+    ValueNotFound,
+    FeatureNotAvailable,
+    Unknown(String),
+}
+
+impl From<String> for FaunaCode {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "instance not found" => FaunaCode::InstanceNotFound,
+            "instance already exists" => FaunaCode::InstanceAlreadyExists,
+            "validation failed" => FaunaCode::ValidationFailed,
+            "instance not unique" => FaunaCode::InstanceNotUnique,
+            "unauthorized" => FaunaCode::Unauthorized,
+            "permission denied" => FaunaCode::PermissionDenied,
+            "transaction aborted" => FaunaCode::TransactionAborted,
+            "value not found" => FaunaCode::ValueNotFound,
+            "feature not available" => FaunaCode::FeatureNotAvailable,
+            _ => FaunaCode::Unknown(code),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fauna_code_known_mapping() {
+        assert_eq!(
+            FaunaCode::from(String::from("instance not found")),
+            FaunaCode::InstanceNotFound,
+        );
+        assert_eq!(
+            FaunaCode::from(String::from("validation failed")),
+            FaunaCode::ValidationFailed,
+        );
+        assert_eq!(
+            FaunaCode::from(String::from("permission denied")),
+            FaunaCode::PermissionDenied,
+        );
+    }
+
+    #[test]
+    fn test_fauna_code_unknown_is_preserved() {
+        assert_eq!(
+            FaunaCode::from(String::from("brand new code")),
+            FaunaCode::Unknown(String::from("brand new code")),
+        );
+    }
+
+    #[test]
+    fn test_fauna_errors_deserialize() {
+        let body = r#"{
+            "errors": [
+                {
+                    "position": ["create_index"],
+                    "code": "validation failed",
+                    "description": "document is not valid.",
+                    "failures": [
+                        {
+                            "field": ["data", "name"],
+                            "code": "value required",
+                            "description": "Value not found."
+                        }
+                    ]
+                }
+            ]
+        }"#;
+
+        let errors: FaunaErrors = serde_json::from_str(body).unwrap();
+
+        assert_eq!(errors.errors.len(), 1);
+
+        let error = &errors.errors[0];
+        assert_eq!(error.code, FaunaCode::ValidationFailed);
+        assert_eq!(error.description, "document is not valid.");
+        assert_eq!(error.failures.len(), 1);
+        assert_eq!(error.failures[0].code, "value required");
+    }
+}
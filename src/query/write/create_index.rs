@@ -2,7 +2,7 @@ use crate::{
     expr::{Expr, IndexPermission, Object},
     query::Query,
 };
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap};
 
 boxed_query!(CreateIndex);
 
@@ -84,11 +84,52 @@ pub struct IndexValue<'a> {
     object: ValueObject<'a>,
 }
 
+/// A source object pairs a class `Ref` with named `fields` bindings. Each
+/// binding is a lambda, evaluated at index time, whose result can be
+/// referenced by name from [`Term::binding`](struct.Term.html#method.binding)
+/// and [`IndexValue::binding`](struct.IndexValue.html#method.binding).
+///
+/// Read the
+/// [docs](https://docs.fauna.com/fauna/current/reference/indexconfig#source-objects)
+#[derive(Debug, Serialize, Clone)]
+pub struct SourceObject<'a> {
+    class: Expr<'a>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<Cow<'a, str>, Expr<'a>>,
+}
+
+impl<'a> SourceObject<'a> {
+    /// Create a source object over a single class `Ref`.
+    pub fn new(class: impl Into<Expr<'a>>) -> Self {
+        Self {
+            class: class.into(),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    /// Bind `name` to a lambda that computes a derived value at index time.
+    pub fn binding<S>(&mut self, name: S, lambda: impl Into<Expr<'a>>) -> &mut Self
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        self.fields.insert(name.into(), lambda.into());
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(untagged)]
+#[doc(hidden)]
+pub enum IndexSource<'a> {
+    Single(Expr<'a>),
+    Multiple(Vec<SourceObject<'a>>),
+}
+
 #[derive(Debug, Serialize, Clone)]
 #[doc(hidden)]
 pub struct IndexParamsInternal<'a> {
     name: Cow<'a, str>,
-    source: Expr<'a>,
+    source: IndexSource<'a>,
     active: bool,
     unique: bool,
     serialized: bool,
@@ -186,7 +227,7 @@ impl<'a> IndexParams<'a> {
         Self {
             object: IndexParamsInternal {
                 name: name.into(),
-                source: source.into(),
+                source: IndexSource::Single(source.into()),
                 active: false,
                 unique: false,
                 serialized: false,
@@ -199,6 +240,17 @@ impl<'a> IndexParams<'a> {
         }
     }
 
+    /// Replace the source with one or many [source objects](struct.SourceObject.html),
+    /// each pairing a class `Ref` with named `fields` bindings.
+    ///
+    /// Use this instead of the single-class source passed to
+    /// [`new`](#method.new) when the index needs computed field bindings or
+    /// spans multiple classes.
+    pub fn sources(&mut self, sources: Vec<SourceObject<'a>>) -> &mut Self {
+        self.object.source = IndexSource::Multiple(sources);
+        self
+    }
+
     /// If set, avoids building the index from relevant instances.
     pub fn active(&mut self) -> &mut Self {
         self.object.active = true;
@@ -334,4 +386,55 @@ mod tests {
 
         assert_eq!(expected, serialized);
     }
+
+    #[test]
+    fn test_create_index_with_source_objects() {
+        let mut source = SourceObject::new(Ref::class("cats"));
+        source.binding("pet_name", Ref::class("dogs"));
+
+        let mut params = IndexParams::new("meows_by_name", Ref::class("cats"));
+        params.sources(vec![source]);
+
+        let query = Query::from(CreateIndex::new(params));
+        let serialized = serde_json::to_value(&query).unwrap();
+
+        let expected = json!({
+            "create_index": {
+                "object": {
+                    "active": false,
+                    "name": "meows_by_name",
+                    "serialized": false,
+                    "source": [
+                        {
+                            "class": {
+                                "@ref": {
+                                    "class": {
+                                        "@ref": {
+                                            "id": "classes",
+                                        },
+                                    },
+                                    "id": "cats",
+                                },
+                            },
+                            "fields": {
+                                "pet_name": {
+                                    "@ref": {
+                                        "class": {
+                                            "@ref": {
+                                                "id": "classes",
+                                            },
+                                        },
+                                        "id": "dogs",
+                                    },
+                                },
+                            },
+                        }
+                    ],
+                    "unique": false,
+                }
+            }
+        });
+
+        assert_eq!(expected, serialized);
+    }
 }